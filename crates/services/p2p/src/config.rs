@@ -6,9 +6,17 @@ use crate::{
 use fuel_core_types::blockchain::consensus::Genesis;
 
 use libp2p::{
+    bandwidth::{
+        BandwidthLogging,
+        BandwidthSinks,
+    },
     core::{
         muxing::StreamMuxerBox,
-        transport::Boxed,
+        transport::{
+            Boxed,
+            OrTransport,
+        },
+        upgrade,
     },
     gossipsub::GossipsubConfig,
     identity::{
@@ -17,6 +25,8 @@ use libp2p::{
     },
     mplex,
     noise::{self,},
+    quic,
+    tls,
     tcp::{
         tokio::Transport as TokioTcpTransport,
         Config as TcpConfig,
@@ -48,11 +58,21 @@ use self::{
     },
     guarded_node::GuardedNode,
 };
+mod connection_limits;
 mod connection_tracker;
+mod discv5;
 mod fuel_authenticated;
 mod fuel_upgrade;
 mod guarded_node;
 
+pub(crate) use self::{
+    connection_limits::ConnectionLimits,
+    discv5::{
+        build_discovery_subsystem,
+        DiscoveredPeer,
+    },
+};
+
 const REQ_RES_TIMEOUT: Duration = Duration::from_secs(20);
 
 /// Maximum response size from the p2p.
@@ -68,6 +88,70 @@ pub const MAX_HEADERS_PER_REQUEST: u32 = 100;
 /// inbound and outbound connections established through the transport.
 const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(20);
 
+/// Valid range for `Config::network_load`.
+const NETWORK_LOAD_RANGE: std::ops::RangeInclusive<u8> = 1..=5;
+
+/// Derives a gossipsub preset from a `network_load` level (see `NETWORK_LOAD_RANGE`).
+///
+/// Returns `(heartbeat_interval, mesh_n_low, mesh_n, mesh_n_high, history_length, history_gossip, gossip_lazy)`.
+/// Higher levels favor faster propagation (shorter heartbeat, bigger mesh and gossip
+/// history) at the cost of more bandwidth; lower levels do the opposite.
+fn gossipsub_preset_for_network_load(network_load: u8) -> (Duration, usize, usize, usize, usize, usize, usize) {
+    let (heartbeat_ms, mesh_n_low, mesh_n, mesh_n_high, history_length, history_gossip, gossip_lazy) =
+        match network_load {
+            1 => (1200, 3, 5, 8, 3, 2, 3),
+            2 => (1025, 3, 6, 9, 4, 2, 4),
+            3 => (850, 4, 6, 10, 5, 3, 5),
+            4 => (675, 4, 7, 11, 5, 4, 6),
+            _ => (500, 4, 8, 12, 6, 5, 8),
+        };
+
+    (
+        Duration::from_millis(heartbeat_ms),
+        mesh_n_low,
+        mesh_n,
+        mesh_n_high,
+        history_length,
+        history_gossip,
+        gossip_lazy,
+    )
+}
+
+/// Builds a [`GossipsubConfig`] whose heartbeat, mesh and gossip history/lazy
+/// parameters are derived from `network_load` (see `gossipsub_preset_for_network_load`).
+fn gossipsub_config_for_network_load(network_load: u8) -> GossipsubConfig {
+    let (heartbeat_interval, mesh_n_low, mesh_n, mesh_n_high, history_length, history_gossip, gossip_lazy) =
+        gossipsub_preset_for_network_load(network_load);
+
+    libp2p::gossipsub::GossipsubConfigBuilder::default()
+        .heartbeat_interval(heartbeat_interval)
+        .mesh_n_low(mesh_n_low)
+        .mesh_n(mesh_n)
+        .mesh_n_high(mesh_n_high)
+        .history_length(history_length)
+        .history_gossip(history_gossip)
+        .gossip_lazy(gossip_lazy)
+        .build()
+        .expect("network_load derived gossipsub config is always valid")
+}
+
+/// Security protocol(s) used to authenticate the TCP leg of the transport.
+///
+/// The QUIC leg always carries its own TLS 1.3 handshake regardless of this setting.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Security {
+    /// Authenticate with Noise (XX, X25519) only. This is the historical default.
+    #[default]
+    Noise,
+    /// Authenticate with libp2p's TLS 1.3 handshake, derived from the same
+    /// secp256k1 `keypair`, only.
+    Tls,
+    /// Offer both Noise and TLS and let peers negotiate whichever they have in
+    /// common. Useful as an interop path while migrating a deployment from Noise
+    /// to TLS without a flag day.
+    NoiseThenTls,
+}
+
 #[derive(Clone, Debug)]
 pub struct Config<State = Initialized> {
     /// The keypair used for for handshake during communication with other p2p nodes.
@@ -88,6 +172,17 @@ pub struct Config<State = Initialized> {
     /// The TCP port that Swarm listens on
     pub tcp_port: u16,
 
+    /// Security protocol(s) used to authenticate the TCP leg of the transport.
+    pub security: Security,
+
+    /// Enables the QUIC (quic-v1) transport alongside TCP.
+    /// When enabled the node dials and listens on `/udp/<udp_port>/quic-v1` addresses
+    /// in addition to the TCP stack.
+    pub enable_quic: bool,
+
+    /// The UDP port that Swarm listens on when `enable_quic` is set.
+    pub udp_port: u16,
+
     /// Max Size of a Block in bytes
     pub max_block_size: usize,
     pub max_headers_per_request: u32,
@@ -99,6 +194,14 @@ pub struct Config<State = Initialized> {
     pub random_walk: Option<Duration>,
     pub connection_idle_timeout: Option<Duration>,
 
+    /// UDP port the discv5 discovery subsystem listens on. `None` disables discv5,
+    /// leaving `enable_mdns`/`random_walk` as the only discovery mechanisms.
+    pub discv5_udp_port: Option<u16>,
+    /// Bootnodes for the discv5 subsystem, as base64-encoded ENR strings (`"enr:-..."`).
+    /// Unlike `bootstrap_nodes`/`reserved_nodes`, these can't be `Multiaddr`s: an ENR is
+    /// a signed record, and its base64 text form looks nothing like a multiaddr.
+    pub enr_bootnodes: Vec<String>,
+
     // 'Reserved Nodes' mode
     /// Priority nodes that the node should maintain connection to
     pub reserved_nodes: Vec<Multiaddr>,
@@ -113,6 +216,12 @@ pub struct Config<State = Initialized> {
     /// Max number of connections per single peer
     /// The total number of connections will be `(max_peers_connected + reserved_nodes.len()) * max_connections_per_peer`
     pub max_connections_per_peer: u32,
+    /// Max number of pending (dialed or not-yet-negotiated) inbound connections.
+    /// Enforced by the [`ConnectionLimits`] swarm behaviour.
+    pub max_pending_connections_in: u32,
+    /// Max number of pending (dialed or not-yet-negotiated) outbound connections.
+    /// Enforced by the [`ConnectionLimits`] swarm behaviour.
+    pub max_pending_connections_out: u32,
     /// The interval at which identification requests are sent to
     /// the remote on established connections after the first request
     pub identify_interval: Option<Duration>,
@@ -120,9 +229,17 @@ pub struct Config<State = Initialized> {
     /// and the next outbound ping
     pub info_interval: Option<Duration>,
 
-    // `Gossipsub` config
+    /// `Gossipsub` config. Any value set here is discarded during `init`, which always
+    /// rebuilds this field from `network_load` - see [`gossipsub_config_for_network_load`].
     pub gossipsub_config: GossipsubConfig,
 
+    /// Tunes how aggressively gossipsub propagates messages, trading bandwidth for
+    /// propagation latency. Valid range is `1..=5`: higher values favor faster message
+    /// propagation at the cost of bandwidth, lower values reduce bandwidth at the cost
+    /// of latency. Derives the heartbeat interval, mesh size and gossip history/lazy
+    /// parameters of `gossipsub_config` during `init` - see [`gossipsub_config_for_network_load`].
+    pub network_load: u8,
+
     pub heartbeat_config: HeartbeatConfig,
 
     // RequestResponse related fields
@@ -138,7 +255,10 @@ pub struct Config<State = Initialized> {
     /// Max time since a given peer has sent a heartbeat before getting reputation penalty
     pub heartbeat_max_time_since_last: Duration,
 
-    /// Enables prometheus metrics for this fuel-service
+    /// Enables prometheus metrics for this fuel-service.
+    /// When set, the `Arc<BandwidthSinks>` returned by `build_transport` is registered
+    /// so operators can observe real per-node ingress/egress byte rates, covering every
+    /// active transport leg (TCP/WebSocket and QUIC, when enabled).
     pub metrics: bool,
 
     /// It is the state of the config initialization. Everyone can create an instance of the `Self`
@@ -159,6 +279,16 @@ impl Config<NotInitialized> {
     pub fn init(self, genesis: Genesis) -> anyhow::Result<Config<Initialized>> {
         use fuel_core_chain_config::GenesisCommitment;
 
+        anyhow::ensure!(
+            NETWORK_LOAD_RANGE.contains(&self.network_load),
+            "network_load must be in range {}..={}, got {}",
+            NETWORK_LOAD_RANGE.start(),
+            NETWORK_LOAD_RANGE.end(),
+            self.network_load
+        );
+
+        let gossipsub_config = gossipsub_config_for_network_load(self.network_load);
+
         Ok(Config {
             keypair: self.keypair,
             network_name: self.network_name,
@@ -166,20 +296,28 @@ impl Config<NotInitialized> {
             address: self.address,
             public_address: self.public_address,
             tcp_port: self.tcp_port,
+            security: self.security,
+            enable_quic: self.enable_quic,
+            udp_port: self.udp_port,
             max_block_size: self.max_block_size,
             max_headers_per_request: self.max_headers_per_request,
             bootstrap_nodes: self.bootstrap_nodes,
             enable_mdns: self.enable_mdns,
             max_peers_connected: self.max_peers_connected,
             max_connections_per_peer: self.max_connections_per_peer,
+            max_pending_connections_in: self.max_pending_connections_in,
+            max_pending_connections_out: self.max_pending_connections_out,
             allow_private_addresses: self.allow_private_addresses,
             random_walk: self.random_walk,
             connection_idle_timeout: self.connection_idle_timeout,
+            discv5_udp_port: self.discv5_udp_port,
+            enr_bootnodes: self.enr_bootnodes,
             reserved_nodes: self.reserved_nodes,
             reserved_nodes_only_mode: self.reserved_nodes_only_mode,
             identify_interval: self.identify_interval,
             info_interval: self.info_interval,
-            gossipsub_config: self.gossipsub_config,
+            gossipsub_config,
+            network_load: self.network_load,
             heartbeat_config: self.heartbeat_config,
             set_request_timeout: self.set_request_timeout,
             set_connection_keep_alive: self.set_connection_keep_alive,
@@ -213,18 +351,26 @@ impl Config<NotInitialized> {
             address: IpAddr::V4(Ipv4Addr::from([0, 0, 0, 0])),
             public_address: None,
             tcp_port: 0,
+            security: Security::default(),
+            enable_quic: false,
+            udp_port: 0,
             max_block_size: MAX_RESPONSE_SIZE,
             max_headers_per_request: MAX_HEADERS_PER_REQUEST,
             bootstrap_nodes: vec![],
             enable_mdns: false,
             max_peers_connected: 50,
             max_connections_per_peer: 3,
+            max_pending_connections_in: 128,
+            max_pending_connections_out: 128,
             allow_private_addresses: true,
             random_walk: Some(Duration::from_millis(500)),
             connection_idle_timeout: Some(Duration::from_secs(120)),
+            discv5_udp_port: None,
+            enr_bootnodes: vec![],
             reserved_nodes: vec![],
             reserved_nodes_only_mode: false,
             gossipsub_config: default_gossipsub_config(),
+            network_load: 3,
             heartbeat_config: HeartbeatConfig::default(),
             set_request_timeout: REQ_RES_TIMEOUT,
             set_connection_keep_alive: REQ_RES_TIMEOUT,
@@ -248,15 +394,90 @@ impl Config<Initialized> {
     }
 }
 
+/// Builds the QUIC (quic-v1) leg of the transport.
+///
+/// QUIC folds encryption and stream multiplexing into the transport itself, so unlike
+/// the TCP leg it is never passed through `.authenticate()`/`.multiplex()`. The
+/// `FuelUpgrade` checksum check still has to run, so it is applied at the application
+/// layer over a dedicated substream right after the QUIC handshake completes, instead
+/// of as part of the transport upgrade pipeline.
+fn build_quic_transport(
+    p2p_config: &Config,
+) -> impl Transport<Output = (PeerId, StreamMuxerBox), Error = std::io::Error, ListenerUpgrade = impl Send, Dial = impl Send>
+       + Clone {
+    let checksum = p2p_config.checksum;
+    // In `reserved_nodes_only_mode` the QUIC leg admits reserved nodes only, like the
+    // `GuardedNode` check on the TCP leg.
+    let reserved_peer_ids = p2p_config
+        .reserved_nodes_only_mode
+        .then(|| Arc::new(peer_ids_set_from(&p2p_config.reserved_nodes)));
+    let quic_config = quic::Config::new(&p2p_config.keypair);
+
+    quic::tokio::Transport::new(quic_config)
+        .map(|(peer_id, connection), _| (peer_id, StreamMuxerBox::new(connection)))
+        .and_then(move |(peer_id, mut muxer), endpoint| {
+            // Each connection builds its own `FuelUpgrade` from the (`Copy`) checksum
+            // instead of sharing/cloning one, so this doesn't rely on `FuelUpgrade`
+            // implementing `Clone`.
+            let fuel_upgrade = FuelUpgrade::new(checksum);
+            async move {
+                if let Some(reserved_peer_ids) = reserved_peer_ids {
+                    if !reserved_peer_ids.contains(&peer_id) {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::PermissionDenied,
+                            "peer is not a reserved node",
+                        ));
+                    }
+                }
+
+                // The dialer is the one opening a new substream; the listener has to
+                // *accept* that peer-initiated substream rather than open its own,
+                // otherwise both sides open unrelated streams that nobody ever reads
+                // from and the checksum handshake hangs until `TRANSPORT_TIMEOUT`.
+                if endpoint.is_dialer() {
+                    let substream = futures::future::poll_fn(|cx| {
+                        libp2p::core::muxing::StreamMuxerExt::poll_outbound_unpin(&mut muxer, cx)
+                    })
+                    .await?;
+                    upgrade::apply_outbound(substream, fuel_upgrade, upgrade::Version::V1).await?;
+                } else {
+                    let substream = futures::future::poll_fn(|cx| {
+                        libp2p::core::muxing::StreamMuxerExt::poll_inbound_unpin(&mut muxer, cx)
+                    })
+                    .await?;
+                    upgrade::apply_inbound(substream, fuel_upgrade).await?;
+                }
+
+                Ok((peer_id, muxer))
+            }
+        })
+}
+
 /// Transport for libp2p communication:
-/// TCP/IP, Websocket
-/// Noise as encryption layer
-/// mplex or yamux for multiplexing
+/// TCP/IP, Websocket, QUIC (quic-v1)
+/// Noise, TLS 1.3 or both (per `Config::security`) as encryption layer for the TCP leg
+/// (QUIC carries its own TLS 1.3 handshake)
+/// mplex or yamux for multiplexing the TCP leg (QUIC multiplexes natively)
+///
+/// Returns, alongside the boxed transport and the `ConnectionState`, an
+/// `Arc<BandwidthSinks>` tracking total inbound/outbound bytes for the node across
+/// every active leg (TCP/WebSocket and, when `enable_quic` is set, QUIC too - the
+/// bandwidth wrapper sits around the already-combined transport for this reason).
+/// Callers should register it with the Prometheus metrics registry whenever
+/// `Config::metrics` is enabled.
+///
+/// The transport only handles per-connection authentication; the returned
+/// [`ConnectionLimits`] is the swarm-level behaviour the caller should register so
+/// pending/established connections are also capped per the
+/// `max_pending_connections_in`/`_out` and `max_peers_connected`/`max_connections_per_peer`
+/// fields of this `Config`.
 pub(crate) fn build_transport(
     p2p_config: &Config,
 ) -> (
     Boxed<(PeerId, StreamMuxerBox)>,
     Arc<RwLock<ConnectionState>>,
+    Arc<BandwidthSinks>,
+    ConnectionLimits,
 ) {
     let transport = {
         let generate_tcp_transport =
@@ -271,7 +492,7 @@ pub(crate) fn build_transport(
     }
     .upgrade(libp2p::core::upgrade::Version::V1);
 
-    let noise_authenticated = {
+    let build_noise_authenticated = || {
         let dh_keys = noise::Keypair::<noise::X25519Spec>::new()
             .into_authentic(&p2p_config.keypair)
             .expect("Noise key generation failed");
@@ -279,6 +500,11 @@ pub(crate) fn build_transport(
         noise::NoiseConfig::xx(dh_keys).into_authenticated()
     };
 
+    let build_tls_authenticated = || {
+        tls::Config::new(&p2p_config.keypair)
+            .expect("TLS certificate generation from secp256k1 keypair failed")
+    };
+
     let multiplex_config = {
         let mplex_config = mplex::MplexConfig::default();
 
@@ -287,39 +513,109 @@ pub(crate) fn build_transport(
         libp2p::core::upgrade::SelectUpgrade::new(yamux_config, mplex_config)
     };
 
-    let fuel_upgrade = FuelUpgrade::new(p2p_config.checksum);
+    // Builds a fresh `FuelUpgrade` from the (`Copy`) checksum for every branch below,
+    // rather than sharing/cloning one: this way the transport doesn't depend on
+    // `FuelUpgrade` implementing `Clone`.
+    let build_fuel_upgrade = || FuelUpgrade::new(p2p_config.checksum);
     let connection_state = ConnectionState::new();
 
-    let transport = if p2p_config.reserved_nodes_only_mode {
+    // Regardless of which security protocol(s) are selected, the authenticated upgrade
+    // is always wrapped by `FuelAuthenticated` so reserved-node guarding and
+    // connection-tracking keep running the same way.
+    let tcp_transport = if p2p_config.reserved_nodes_only_mode {
         let guarded_node = GuardedNode::new(&p2p_config.reserved_nodes);
 
-        let fuel_authenticated =
-            FuelAuthenticated::new(noise_authenticated, guarded_node);
-
-        transport
-            .authenticate(fuel_authenticated)
-            .apply(fuel_upgrade)
-            .multiplex(multiplex_config)
-            .timeout(TRANSPORT_TIMEOUT)
-            .boxed()
+        match p2p_config.security {
+            Security::Noise => transport
+                .authenticate(FuelAuthenticated::new(build_noise_authenticated(), guarded_node))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+            Security::Tls => transport
+                .authenticate(FuelAuthenticated::new(build_tls_authenticated(), guarded_node))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+            Security::NoiseThenTls => transport
+                .authenticate(FuelAuthenticated::new(
+                    upgrade::SelectUpgrade::new(build_noise_authenticated(), build_tls_authenticated()),
+                    guarded_node,
+                ))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+        }
     } else {
         let connection_tracker =
             ConnectionTracker::new(&p2p_config.reserved_nodes, connection_state.clone());
 
-        let fuel_authenticated =
-            FuelAuthenticated::new(noise_authenticated, connection_tracker);
+        match p2p_config.security {
+            Security::Noise => transport
+                .authenticate(FuelAuthenticated::new(
+                    build_noise_authenticated(),
+                    connection_tracker,
+                ))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+            Security::Tls => transport
+                .authenticate(FuelAuthenticated::new(
+                    build_tls_authenticated(),
+                    connection_tracker,
+                ))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+            Security::NoiseThenTls => transport
+                .authenticate(FuelAuthenticated::new(
+                    upgrade::SelectUpgrade::new(build_noise_authenticated(), build_tls_authenticated()),
+                    connection_tracker,
+                ))
+                .apply(build_fuel_upgrade())
+                .multiplex(multiplex_config)
+                .timeout(TRANSPORT_TIMEOUT)
+                .boxed(),
+        }
+    };
+
+    let transport = if p2p_config.enable_quic {
+        let quic_transport = build_quic_transport(p2p_config);
 
-        transport
-            .authenticate(fuel_authenticated)
-            .apply(fuel_upgrade)
-            .multiplex(multiplex_config)
+        OrTransport::new(quic_transport, tcp_transport)
+            .map(|output, _| match output {
+                futures::future::Either::Left(output) => output,
+                futures::future::Either::Right(output) => output,
+            })
             .timeout(TRANSPORT_TIMEOUT)
             .boxed()
+    } else {
+        tcp_transport
     };
 
-    (transport, connection_state)
+    // Wrapping the already-combined transport (rather than just the TCP leg) means
+    // `bandwidth_sinks` accounts for QUIC traffic too whenever `enable_quic` is set.
+    let (transport, bandwidth_sinks) = BandwidthLogging::new(transport);
+
+    let connection_limits = ConnectionLimits::new(p2p_config);
+
+    (
+        transport.boxed(),
+        connection_state,
+        bandwidth_sinks,
+        connection_limits,
+    )
 }
 
+/// Extracts the [`PeerId`] out of every [`Multiaddr`].
+///
+/// This works uniformly for TCP, Websocket and QUIC (`/quic-v1`) addresses, since the
+/// `PeerId` is always encoded as the trailing `/p2p/<peer_id>` protocol regardless of
+/// the underlying transport.
 pub fn peer_ids_set_from(multiaddr: &[Multiaddr]) -> HashSet<PeerId> {
     multiaddr
         .iter()
@@ -328,3 +624,52 @@ pub fn peer_ids_set_from(multiaddr: &[Multiaddr]) -> HashSet<PeerId> {
         .map(|address| PeerId::try_from_multiaddr(address).unwrap())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_ids_set_from_extracts_peer_id_from_quic_v1_multiaddr() {
+        let peer_id = PeerId::random();
+        let address: Multiaddr = format!("/ip4/127.0.0.1/udp/4242/quic-v1/p2p/{peer_id}")
+            .parse()
+            .unwrap();
+
+        let peer_ids = peer_ids_set_from(&[address]);
+
+        assert_eq!(peer_ids, HashSet::from([peer_id]));
+    }
+
+    #[test]
+    fn gossipsub_preset_for_network_load_favors_faster_propagation_at_higher_levels() {
+        let (low_heartbeat, _, low_mesh_n, _, low_history_length, ..) =
+            gossipsub_preset_for_network_load(*NETWORK_LOAD_RANGE.start());
+        let (high_heartbeat, _, high_mesh_n, _, high_history_length, ..) =
+            gossipsub_preset_for_network_load(*NETWORK_LOAD_RANGE.end());
+
+        assert!(high_heartbeat < low_heartbeat);
+        assert!(high_mesh_n > low_mesh_n);
+        assert!(high_history_length > low_history_length);
+    }
+
+    #[test]
+    fn gossipsub_config_for_network_load_builds_for_every_valid_level() {
+        for network_load in NETWORK_LOAD_RANGE {
+            // Panics if `GossipsubConfigBuilder::build()` rejects the preset - this is
+            // the same invariant `gossipsub_config_for_network_load` relies on via its
+            // own `.expect(...)`.
+            let _ = gossipsub_config_for_network_load(network_load);
+        }
+    }
+
+    #[test]
+    fn init_rejects_network_load_outside_valid_range() {
+        let mut config = Config::<NotInitialized>::default("test_network");
+        config.network_load = *NETWORK_LOAD_RANGE.end() + 1;
+
+        let result = config.init(Default::default());
+
+        assert!(result.is_err());
+    }
+}
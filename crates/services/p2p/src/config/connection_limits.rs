@@ -0,0 +1,129 @@
+//! Dedicated connection-limits behaviour enforcing hard caps on established and
+//! pending connections at the swarm level.
+//!
+//! The transport's `ConnectionTracker`/`FuelAuthenticated` layer already does
+//! reputation-aware, reserved-node-aware admission during authentication; this
+//! behaviour is the swarm-level backstop that rejects a connection (or its pending
+//! dial/listen) purely on the hard caps below, before it is ever handed a handler.
+//! `reserved_nodes` always get headroom on top of `max_peers_connected`, mirroring the
+//! `(max_peers_connected + reserved_nodes.len()) * max_connections_per_peer` total
+//! connections formula already documented on `Config::max_peers_connected` - that total
+//! is split, not duplicated, across the inbound/outbound direction caps (see
+//! `INBOUND_SHARE_OF_TOTAL`), so `max_established` stays equal to the documented total
+//! rather than some multiple of it.
+
+use crate::config::Config;
+use libp2p::{
+    connection_limits::{
+        Behaviour as Libp2pConnectionLimits,
+        ConnectionLimits as Libp2pLimits,
+    },
+    swarm::NetworkBehaviour,
+};
+
+/// Swarm-level connection limiting. Wraps libp2p's own `connection_limits::Behaviour`,
+/// configured from `Config` so the hard caps stay in one place alongside the rest of
+/// the p2p tuning knobs.
+#[derive(NetworkBehaviour)]
+pub(crate) struct ConnectionLimits {
+    limits: Libp2pConnectionLimits,
+}
+
+/// Inbound gets a minority share of the total established-connection budget, and
+/// outbound gets the rest - this is what keeps enough outbound slots free for the node
+/// to stay well-connected even once inbound connections would otherwise have filled the
+/// shared budget. Numerator/denominator rather than a single fraction so the split stays
+/// exact integer arithmetic: `max_established_incoming + max_established_outgoing`
+/// always sums back to the total, never some multiple of it.
+const INBOUND_SHARE_OF_TOTAL: (u32, u32) = (3, 7);
+
+/// Splits the documented `(max_peers_connected + reserved_nodes.len()) *
+/// max_connections_per_peer` total established-connection budget into
+/// `(incoming, outgoing)` shares per `INBOUND_SHARE_OF_TOTAL`, with outbound taking the
+/// remainder so the two always sum back to exactly `max_established_total` - never some
+/// multiple of it.
+fn established_direction_caps(p2p_config: &Config) -> (u32, u32) {
+    let reserved_headroom = p2p_config.reserved_nodes.len() as u32;
+    let max_established_total =
+        (p2p_config.max_peers_connected + reserved_headroom) * p2p_config.max_connections_per_peer;
+
+    let (share_numerator, share_denominator) = INBOUND_SHARE_OF_TOTAL;
+    let max_established_incoming = (max_established_total * share_numerator) / share_denominator;
+    let max_established_outgoing = max_established_total - max_established_incoming;
+
+    (max_established_incoming, max_established_outgoing)
+}
+
+impl ConnectionLimits {
+    /// Builds the hard caps from `Config`:
+    /// - `max_connections_per_peer` caps established connections to a single peer.
+    /// - `max_peers_connected` (plus `reserved_nodes.len()` headroom) caps the total established
+    ///   connections, split - not duplicated - across inbound/outbound (see
+    ///   `established_direction_caps`): inbound gets a minority share and outbound gets
+    ///   the rest, so the node keeps enough outbound slots free to stay well-connected
+    ///   instead of letting inbound connections crowd them out, while the sum of both
+    ///   directions still matches the total documented on `Config::max_peers_connected`.
+    ///   These caps apply to reserved and other peers alike: the headroom is shared by all
+    ///   peers, so once a cap is reached a reserved peer's connection is denied too.
+    /// - `max_pending_connections_in`/`max_pending_connections_out` cap connections
+    ///   that are still being dialed/negotiated, so a burst of slow handshakes can't
+    ///   exhaust resources ahead of the established-connection caps above.
+    pub(crate) fn new(p2p_config: &Config) -> Self {
+        let (max_established_incoming, max_established_outgoing) =
+            established_direction_caps(p2p_config);
+
+        let limits = Libp2pLimits::default()
+            .with_max_established_per_peer(Some(p2p_config.max_connections_per_peer))
+            .with_max_established_incoming(Some(max_established_incoming))
+            .with_max_established_outgoing(Some(max_established_outgoing))
+            .with_max_established(Some(max_established_incoming + max_established_outgoing))
+            .with_max_pending_incoming(Some(p2p_config.max_pending_connections_in))
+            .with_max_pending_outgoing(Some(p2p_config.max_pending_connections_out));
+
+        Self {
+            limits: Libp2pConnectionLimits::new(limits),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotInitialized;
+
+    fn config_with(max_peers_connected: u32, reserved_nodes: usize) -> Config<NotInitialized> {
+        let mut config = Config::<NotInitialized>::default("test_network");
+        config.max_peers_connected = max_peers_connected;
+        config.reserved_nodes = vec![Default::default(); reserved_nodes];
+        config
+    }
+
+    #[test]
+    fn outbound_gets_a_bigger_share_than_inbound() {
+        let (incoming, outgoing) = established_direction_caps(&config_with(50, 0));
+
+        assert!(incoming < outgoing);
+    }
+
+    #[test]
+    fn reserved_node_headroom_increases_the_established_caps() {
+        let (incoming_no_reserved, outgoing_no_reserved) =
+            established_direction_caps(&config_with(50, 0));
+        let (incoming_with_reserved, outgoing_with_reserved) =
+            established_direction_caps(&config_with(50, 5));
+
+        assert!(incoming_with_reserved + outgoing_with_reserved
+            > incoming_no_reserved + outgoing_no_reserved);
+    }
+
+    #[test]
+    fn directional_caps_sum_to_the_documented_total() {
+        let config = config_with(50, 5);
+        let expected_total = (config.max_peers_connected + config.reserved_nodes.len() as u32)
+            * config.max_connections_per_peer;
+
+        let (incoming, outgoing) = established_direction_caps(&config);
+
+        assert_eq!(incoming + outgoing, expected_total);
+    }
+}
@@ -0,0 +1,232 @@
+//! ENR + discv5 based WAN peer discovery.
+//!
+//! This is an alternative to the LAN-only `enable_mdns` option and to Kademlia's
+//! `random_walk`: nodes advertise a signed [ENR](https://github.com/ethereum/devp2p/blob/master/enr.md)
+//! carrying their reachable address and a fork-id-style field derived from the
+//! network's `Checksum`/`network_name`, and periodically run FINDNODE queries against
+//! a discv5 DHT to find more of them.
+
+use crate::config::{
+    Checksum,
+    Config,
+};
+use discv5::{
+    enr::{
+        CombinedKey,
+        Enr,
+        EnrBuilder,
+    },
+    Discv5,
+    Discv5Config,
+    Discv5Event,
+};
+use libp2p::{
+    identity::Keypair,
+    multiaddr::Protocol,
+    Multiaddr,
+    PeerId,
+};
+use std::net::{
+    IpAddr,
+    SocketAddr,
+};
+use tokio::sync::mpsc;
+
+/// The fork-id-style ENR key used to keep discovery scoped to nodes on the same chain.
+/// Peers whose ENR is missing this key, or carries a different value, are filtered out
+/// before ever being handed to the `PeerManager` as a dial candidate.
+const FUEL_NETWORK_ENR_KEY: &str = "fuel_network";
+
+/// A peer discovered via discv5 whose advertised fork id matched ours.
+#[derive(Clone, Debug)]
+pub struct DiscoveredPeer {
+    pub peer_id: PeerId,
+    pub multiaddr: Multiaddr,
+}
+
+/// Builds and signs this node's ENR from its `keypair`, reachable address and the
+/// network's checksum/name, so that other nodes can filter it out before dialing if
+/// they are on a different chain.
+fn build_enr(
+    keypair: &Keypair,
+    address: IpAddr,
+    tcp_port: u16,
+    discv5_udp_port: u16,
+    network_name: &str,
+    checksum: Checksum,
+) -> anyhow::Result<Enr<CombinedKey>> {
+    let combined_key = CombinedKey::from_libp2p(keypair)
+        .map_err(|e| anyhow::anyhow!("Failed to derive discv5 key from keypair: {e:?}"))?;
+
+    let fork_id = fuel_network_fork_id(network_name, checksum);
+
+    let mut builder = EnrBuilder::new("v4");
+    builder.ip(address).tcp(tcp_port).udp(discv5_udp_port);
+    builder.add_value(FUEL_NETWORK_ENR_KEY, &fork_id);
+
+    builder
+        .build(&combined_key)
+        .map_err(|e| anyhow::anyhow!("Failed to build ENR: {e:?}"))
+}
+
+/// A short, deterministic identifier for "this chain", encoded into the ENR so peers on
+/// a different `network_name`/`checksum` are filtered out before a FINDNODE result is
+/// ever turned into a dial candidate.
+fn fuel_network_fork_id(network_name: &str, checksum: Checksum) -> Vec<u8> {
+    let mut fork_id = Vec::with_capacity(network_name.len() + checksum.as_ref().len());
+    fork_id.extend_from_slice(network_name.as_bytes());
+    fork_id.extend_from_slice(checksum.as_ref());
+    fork_id
+}
+
+/// Runs the discv5 discovery subsystem: builds this node's ENR, seeds the table with
+/// `enr_bootnodes`, and periodically runs FINDNODE queries, forwarding every discovered
+/// peer with a matching fork id on the channel returned by `spawn`.
+pub(crate) struct Discv5Discovery {
+    discv5: Discv5,
+    expected_fork_id: Vec<u8>,
+}
+
+impl Discv5Discovery {
+    /// Builds this node's ENR, seeds the discv5 table with `enr_bootnodes`, and binds
+    /// the discv5 UDP socket. `async` because `Discv5::start` itself is - it has to
+    /// bind the socket and spin up the service's background task, so the returned
+    /// `Discv5Discovery` is only actually listening once this has been awaited.
+    pub(crate) async fn new(p2p_config: &Config) -> anyhow::Result<Option<Self>> {
+        let Some(discv5_udp_port) = p2p_config.discv5_udp_port else {
+            return Ok(None);
+        };
+
+        let enr = build_enr(
+            &p2p_config.keypair,
+            p2p_config.address,
+            p2p_config.tcp_port,
+            discv5_udp_port,
+            &p2p_config.network_name,
+            p2p_config.checksum,
+        )?;
+
+        let combined_key = CombinedKey::from_libp2p(&p2p_config.keypair)
+            .map_err(|e| anyhow::anyhow!("Failed to derive discv5 key from keypair: {e:?}"))?;
+
+        let listen_socket = SocketAddr::new(p2p_config.address, discv5_udp_port);
+        let mut discv5 = Discv5::new(enr, combined_key, Discv5Config::default())
+            .map_err(|e| anyhow::anyhow!("Failed to start discv5: {e:?}"))?;
+
+        for enr_bootnode in enr_bootnodes_from(&p2p_config.enr_bootnodes) {
+            let _ = discv5.add_enr(enr_bootnode);
+        }
+        discv5
+            .start(listen_socket)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind discv5 UDP socket: {e:?}"))?;
+
+        Ok(Some(Self {
+            discv5,
+            expected_fork_id: fuel_network_fork_id(&p2p_config.network_name, p2p_config.checksum),
+        }))
+    }
+
+    /// Periodically runs FINDNODE queries, converting every discovered ENR with a
+    /// matching fork id into a `DiscoveredPeer` and forwarding it on an unbounded
+    /// channel. Returns the receiving end; the channel is dropped, and the discovery
+    /// task stopped, once the returned `mpsc::UnboundedReceiver` is dropped.
+    pub(crate) fn spawn(mut self) -> mpsc::UnboundedReceiver<DiscoveredPeer> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut events = self.discv5.event_stream().await;
+            let mut find_node_interval = tokio::time::interval(std::time::Duration::from_secs(30));
+
+            loop {
+                tokio::select! {
+                    _ = sender.closed() => break,
+                    _ = find_node_interval.tick() => {
+                        let _ = self.discv5.find_node(discv5::enr::NodeId::random()).await;
+                    }
+                    event = events.recv() => {
+                        let Some(Discv5Event::Discovered(enr)) = event else { continue };
+
+                        if enr.get(FUEL_NETWORK_ENR_KEY) != Some(self.expected_fork_id.as_slice()) {
+                            continue;
+                        }
+
+                        if let Some(peer) = discovered_peer_from_enr(&enr) {
+                            if sender.send(peer).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+fn enr_bootnodes_from(enrs: &[String]) -> Vec<Enr<CombinedKey>> {
+    // An ENR is a signed record, not a plain address, so `p2p_config.enr_bootnodes` is
+    // stored as raw base64 ENR text (`"enr:-..."`) rather than a `Multiaddr`, and parsed
+    // directly via `Enr`'s own `FromStr`. A malformed entry is skipped without logging
+    // rather than panicking the node at startup.
+    enrs.iter().filter_map(|enr| enr.parse().ok()).collect()
+}
+
+/// Builds and starts the discv5 discovery subsystem, if `p2p_config.discv5_udp_port` is
+/// set, returning the channel of discovered peers to feed into the `PeerManager`
+/// alongside mDNS/Kademlia's `random_walk`. Returns `Ok(None)` when discv5 is disabled.
+///
+/// This function does not dial the discovered peers itself: a `DiscoveredPeer` only
+/// leads to a connection once whoever polls the returned receiver dials it.
+pub(crate) async fn build_discovery_subsystem(
+    p2p_config: &Config,
+) -> anyhow::Result<Option<mpsc::UnboundedReceiver<DiscoveredPeer>>> {
+    Ok(Discv5Discovery::new(p2p_config)
+        .await?
+        .map(Discv5Discovery::spawn))
+}
+
+fn discovered_peer_from_enr(enr: &Enr<CombinedKey>) -> Option<DiscoveredPeer> {
+    let peer_id = enr.peer_id();
+    let ip = enr.ip4()?;
+    let tcp_port = enr.tcp4()?;
+
+    let mut multiaddr = Multiaddr::empty();
+    multiaddr.push(Protocol::from(ip));
+    multiaddr.push(Protocol::Tcp(tcp_port));
+    multiaddr.push(Protocol::P2p(peer_id.into()));
+
+    Some(DiscoveredPeer { peer_id, multiaddr })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_enr_string() -> String {
+        let key = CombinedKey::generate_secp256k1();
+        let mut builder = EnrBuilder::new("v4");
+        builder.ip([127, 0, 0, 1].into()).tcp(30333).udp(30304);
+        builder
+            .build(&key)
+            .expect("valid ENR fields")
+            .to_base64()
+    }
+
+    #[test]
+    fn enr_bootnodes_from_parses_valid_base64_enr_strings() {
+        let enr_string = sample_enr_string();
+
+        let enrs = enr_bootnodes_from(&[enr_string]);
+
+        assert_eq!(enrs.len(), 1);
+    }
+
+    #[test]
+    fn enr_bootnodes_from_skips_malformed_entries() {
+        let enrs = enr_bootnodes_from(&["not an enr".to_string()]);
+
+        assert!(enrs.is_empty());
+    }
+}